@@ -9,8 +9,11 @@ use git2::{
     Repository,
     Time
 };
+use std::fs;
+use std::path::Path;
 use std::str;
-use std::collections::HashMap;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use indexmap::IndexMap;
 use regex::Regex;
 use lazy_static::lazy_static;
 use docopt::Docopt;
@@ -21,7 +24,238 @@ static VERSION: &str = env!("CARGO_PKG_VERSION");
 
 lazy_static! {
     static ref VERSION_REGEX: Regex = Regex::new(r"^(\d+)\.(\d+)\.(\d+)(-\w+)?(-SNAPSHOT)?$").unwrap();
-    static ref GITHUB_URL_REGEX: Regex = Regex::new(r"^git@([\w.]*):([\w/-]*)\.git$").unwrap();
+    static ref SCP_URL_REGEX: Regex = Regex::new(r"^[\w.-]+@([\w.-]+):(.+?)(?:\.git)?$").unwrap();
+    static ref SSH_URL_REGEX: Regex = Regex::new(r"^ssh://(?:[\w.-]+@)?([\w.-]+)(?::\d+)?/(.+?)(?:\.git)?$").unwrap();
+    static ref HTTPS_URL_REGEX: Regex = Regex::new(r"^https?://(?:[\w.-]+@)?([\w.-]+)/(.+?)(?:\.git)?$").unwrap();
+    static ref ISSUE_REF_REGEX: Regex = Regex::new(r"#(\d+)").unwrap();
+    static ref CLOSES_REGEX: Regex = Regex::new(r"(?i)^(?:Closes|Fixes|Resolves)\s+#(\d+)").unwrap();
+}
+
+/// Which git hosting service a repo's `origin` remote points at, used to
+/// pick the right URL shapes for commit, compare, and issue links.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RemoteHost {
+    GitHub,
+    GitLab,
+    Bitbucket,
+}
+
+impl RemoteHost {
+    /// Guesses the host from a repo's hostname (not its full URL, so an org
+    /// or repo name like `gitlab-ci-templates` can't cause a misdetection).
+    /// Anything unrecognized (self-hosted GitHub Enterprise, plain Git
+    /// servers) falls back to GitHub's URL shape.
+    fn detect(hostname: &str) -> RemoteHost {
+        if hostname.contains("gitlab") {
+            RemoteHost::GitLab
+        } else if hostname.contains("bitbucket") {
+            RemoteHost::Bitbucket
+        } else {
+            RemoteHost::GitHub
+        }
+    }
+
+    fn from_config(name: &str) -> Option<RemoteHost> {
+        match name {
+            "github" => Some(RemoteHost::GitHub),
+            "gitlab" => Some(RemoteHost::GitLab),
+            "bitbucket" => Some(RemoteHost::Bitbucket),
+            _ => None,
+        }
+    }
+
+    fn commit_url(&self, repo_url: &str, sha: &str) -> String {
+        match self {
+            RemoteHost::GitLab => format!("{}/-/commit/{}", repo_url, sha),
+            RemoteHost::Bitbucket => format!("{}/commits/{}", repo_url, sha),
+            RemoteHost::GitHub => format!("{}/commit/{}", repo_url, sha),
+        }
+    }
+
+    fn compare_url(&self, repo_url: &str, from: &str, to: &str) -> String {
+        match self {
+            RemoteHost::GitLab => format!("{}/-/compare/{}...{}", repo_url, from, to),
+            RemoteHost::Bitbucket => format!("{}/branches/compare/{}..{}", repo_url, to, from),
+            RemoteHost::GitHub => format!("{}/compare/{}...{}", repo_url, from, to),
+        }
+    }
+
+    fn issue_url(&self, repo_url: &str, number: &str) -> String {
+        match self {
+            RemoteHost::GitLab => format!("{}/-/issues/{}", repo_url, number),
+            RemoteHost::Bitbucket => format!("{}/issues/{}", repo_url, number),
+            RemoteHost::GitHub => format!("{}/issues/{}", repo_url, number),
+        }
+    }
+}
+
+/// Rewrites `#123`-style references in `text` into markdown links pointing
+/// at the repo's issue tracker (GitHub resolves issue links to PRs too).
+fn linkify_issue_refs(text: &str, repo_url: &str, host: RemoteHost) -> String {
+    ISSUE_REF_REGEX.replace_all(text, |caps: &regex::Captures| {
+        format!("[#{}]({})", &caps[1], host.issue_url(repo_url, &caps[1]))
+    }).to_string()
+}
+
+/// Returns the issue numbers closed by a commit, parsed from `Closes #123` /
+/// `Fixes #123` / `Resolves #123` footer lines in its body.
+fn find_closed_issues(commit_messages: &str) -> Vec<String> {
+    let mut issues = Vec::new();
+    for line in commit_messages.lines().skip(1) {
+        if let Some(cap) = CLOSES_REGEX.captures(line.trim()) {
+            issues.push(cap[1].to_string());
+        }
+    }
+    issues
+}
+
+/// Returns a string listing the issues closed by `commits`, parsed from
+/// their `Closes #N` footers. Empty when none were found.
+fn get_closed_issues(commits: &Vec<Commit>, submodule: &str, repo_url: &str, config: &Config, host: RemoteHost) -> String {
+    let mut issues: Vec<String> = Vec::new();
+    for commit in commits {
+        let commit_messages = String::from_utf8_lossy(commit.message_bytes());
+        let commit_title = commit_messages.lines().next().expect("Couldn't read the commit's title.");
+
+        if config.should_skip(commit_title) {
+            continue;
+        }
+        if submodule != "" && !commit_title.contains(&format!("({})", submodule)) {
+            continue;
+        }
+
+        for issue in find_closed_issues(&commit_messages) {
+            if !issues.contains(&issue) {
+                issues.push(issue);
+            }
+        }
+    }
+
+    if issues.is_empty() {
+        return String::new();
+    }
+
+    let links: Vec<String> = issues.iter()
+        .map(|issue| format!("[#{}]({})", issue, host.issue_url(repo_url, issue)))
+        .collect();
+    format!("### Closes:\n\n{}\n\n\n", links.join(", "))
+}
+
+/// Display settings for a single commit category, e.g. `feat` or `fix`.
+#[derive(Debug, Clone)]
+struct CategoryConfig {
+    title: String,
+    skip: bool,
+}
+
+/// One `[[categories]]` entry in `.gitrelease.toml`.
+///
+/// Parsed as an array of tables (rather than a `[categories]` table keyed by
+/// commit type) so that file order is preserved: `indexmap`'s `Deserialize`
+/// impl is gated behind a Cargo feature this crate doesn't enable, and plain
+/// TOML tables don't remember the order their keys were written in, but
+/// arrays always do.
+#[derive(Debug, Deserialize)]
+struct CategoryEntry {
+    #[serde(rename = "type")]
+    doc_type: String,
+    title: String,
+    #[serde(default)]
+    skip: bool,
+}
+
+/// User-configurable settings, loaded from `.gitrelease.toml`.
+///
+/// The `categories` map is read in file order, so it doubles as the priority
+/// order that `get_categorized_changes` renders sections in.
+#[derive(Debug, Deserialize)]
+struct Config {
+    #[serde(default = "Config::default_categories", deserialize_with = "Config::deserialize_categories")]
+    categories: IndexMap<String, CategoryConfig>,
+    repository_url: Option<String>,
+    /// Forces the remote host kind ("github", "gitlab", or "bitbucket")
+    /// instead of guessing it from the remote's hostname. Needed for
+    /// self-hosted instances whose hostname doesn't hint at the product.
+    remote_host: Option<String>,
+    #[serde(default = "Config::default_skip_prefixes")]
+    skip_prefixes: Vec<String>,
+}
+
+impl Config {
+    /// The category labels gitrelease has always shipped with.
+    fn default_categories() -> IndexMap<String, CategoryConfig> {
+        let mut categories = IndexMap::new();
+        categories.insert("feat".to_string(), CategoryConfig{title: "Features".to_string(), skip: false});
+        categories.insert("fix".to_string(), CategoryConfig{title: "Bug Fixes".to_string(), skip: false});
+        categories.insert("perf".to_string(), CategoryConfig{title: "Performance Improvements".to_string(), skip: false});
+        categories.insert("docs".to_string(), CategoryConfig{title: "Documentation".to_string(), skip: false});
+        categories.insert("style".to_string(), CategoryConfig{title: "Styles".to_string(), skip: true});
+        categories.insert("refactor".to_string(), CategoryConfig{title: "Code Refactoring".to_string(), skip: true});
+        categories.insert("test".to_string(), CategoryConfig{title: "Test Refactoring".to_string(), skip: true});
+        categories.insert("chore".to_string(), CategoryConfig{title: "Miscellaneous Chores".to_string(), skip: true});
+        categories
+    }
+
+    /// Deserializes the `[[categories]]` array into an order-preserving map
+    /// keyed by commit type.
+    fn deserialize_categories<'de, D>(deserializer: D) -> Result<IndexMap<String, CategoryConfig>, D::Error>
+    where D: serde::Deserializer<'de> {
+        let entries = Vec::<CategoryEntry>::deserialize(deserializer)?;
+        let mut categories = IndexMap::new();
+        for entry in entries {
+            categories.insert(entry.doc_type, CategoryConfig{title: entry.title, skip: entry.skip});
+        }
+        Ok(categories)
+    }
+
+    /// Commit subject prefixes that are never included in a release.
+    fn default_skip_prefixes() -> Vec<String> {
+        vec!["Release".to_string()]
+    }
+
+    fn default_config() -> Config {
+        Config {
+            categories: Config::default_categories(),
+            repository_url: None,
+            remote_host: None,
+            skip_prefixes: Config::default_skip_prefixes(),
+        }
+    }
+
+    /// Loads `.gitrelease.toml` from `config_path`, or from `repo_root` if
+    /// `config_path` isn't given. Falls back to the built-in defaults when
+    /// no config file is found.
+    fn load(repo_root: &str, config_path: &str) -> Config {
+        let path = match config_path {
+            "" => Path::new(repo_root).join(".gitrelease.toml"),
+            _ => Path::new(config_path).to_path_buf(),
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).expect("Couldn't parse .gitrelease.toml."),
+            Err(_) => Config::default_config(),
+        }
+    }
+
+    /// Returns whether `commit_title` starts with one of the configured skip
+    /// prefixes, e.g. "Release".
+    fn should_skip(&self, commit_title: &str) -> bool {
+        self.skip_prefixes.iter().any(|prefix| commit_title.starts_with(prefix.as_str()))
+    }
+
+    /// Returns the display title and skip flag for a commit type, defaulting
+    /// to an "Other" title with skip set for types the config doesn't list.
+    fn category(&self, doc_type: &str) -> (&str, bool) {
+        match self.categories.get(doc_type) {
+            Some(category) => (category.title.as_str(), category.skip),
+            None => ("Other", true),
+        }
+    }
+
+    /// Returns the configured remote host override, if any.
+    fn remote_host(&self) -> Option<RemoteHost> {
+        self.remote_host.as_deref().and_then(RemoteHost::from_config)
+    }
 }
 
 /// Finds out the most recent commit in the repository.
@@ -51,21 +285,23 @@ fn find_commits_in_range(repo: &Repository, start: git2::Oid, end: git2::Oid) ->
 }
 
 /// Returns a string for printing all commits since last release.
-fn get_commits(commits: &Vec<Commit>, submodule: &str, repo_url: &str) -> String {
+fn get_commits(commits: &Vec<Commit>, submodule: &str, repo_url: &str, config: &Config, host: RemoteHost) -> String {
     let mut result = String::from("### Commits since last release:\n\n");
 
     for commit in commits {
         let commit_messages = String::from_utf8_lossy(commit.message_bytes());
         let commit_title = commit_messages.lines().next().unwrap();
 
-        let commit_url = format!("{}/commit/{}", repo_url, commit.id());
+        let commit_url = host.commit_url(repo_url, &commit.id().to_string());
 
-        if commit_title.starts_with("Release") {
+        if config.should_skip(commit_title) {
             continue;
         }
 
         if submodule == "" || commit_title.contains(&format!("({})", submodule)) {
-            result.push_str(&format!("* [{}]({})\n", commit_title, commit_url));
+            let linked_title = linkify_issue_refs(commit_title, repo_url, host);
+            let short_sha = &commit.id().to_string()[..7];
+            result.push_str(&format!("* {} ([{}]({}))\n", linked_title, short_sha, commit_url));
         }
     }
 
@@ -125,32 +361,70 @@ fn find_commit_for_last_release(repo: &Repository, folder: &str) -> Option<Tag>
     latest_tag
 }
 
-/// Returns a hash table of categorized commits based on the change type, e.g.,
-/// feat, fix, docs, etc.
-fn get_category_table(commits: &Vec<Commit>, submodule: &str) -> HashMap<String, Vec<String>> {
-    let mut table: HashMap<String, Vec<String>> = HashMap::new();
+/// The key used in the category table to bucket breaking changes.
+const BREAKING_CHANGE_KEY: &str = "breaking";
+
+/// Returns the breaking-change description for a commit, if any.
+///
+/// A commit is considered breaking when its subject has a `!` immediately
+/// before the `:` (e.g. `feat!:` or `fix(api)!:`), or when its body/footer
+/// has a line starting with `BREAKING CHANGE:`. The footer text is preferred
+/// over the subject when both are present.
+fn find_breaking_change(commit_messages: &str, commit_title: &str, colon_index: usize) -> Option<String> {
+    for line in commit_messages.lines().skip(1) {
+        if let Some(text) = line.strip_prefix("BREAKING CHANGE:") {
+            return Some(text.trim().to_string());
+        }
+    }
+
+    if colon_index > 0 && commit_title.as_bytes()[colon_index - 1] == b'!' {
+        return Some(commit_title[colon_index+1..].trim().to_string());
+    }
+
+    None
+}
+
+/// Returns a table of categorized commits based on the change type, e.g.,
+/// feat, fix, docs, etc. Breaking changes are additionally bucketed under
+/// `BREAKING_CHANGE_KEY`.
+///
+/// An `IndexMap` is used instead of a `HashMap` so that categories and the
+/// commits within them keep the order they were first seen in, which keeps
+/// the generated output reproducible between runs.
+fn get_category_table(commits: &Vec<Commit>, submodule: &str, config: &Config, repo_url: &str, host: RemoteHost) -> IndexMap<String, Vec<String>> {
+    let mut table: IndexMap<String, Vec<String>> = IndexMap::new();
 
     for commit in commits {
         let commit_messages = String::from_utf8_lossy(commit.message_bytes());
         let commit_title = commit_messages.lines().next().expect("Couldn't read the commit's title.");
-        if commit_title.starts_with("Release") {
+        if config.should_skip(commit_title) {
             continue;
         }
 
         if submodule == "" || commit_title.contains(&format!("({})", submodule)) {
             if let Some(index) = commit_title.find(':') {
-                let title = &commit_title[index+1..].trim();
+                let has_bang = index > 0 && commit_title.as_bytes()[index - 1] == b'!';
+                let title = linkify_issue_refs(commit_title[index+1..].trim(), repo_url, host);
 
                 let end_index = match commit_title.find("(") {
                     Some(i) => i,
-                    None => index
+                    None => if has_bang { index - 1 } else { index }
                 };
                 let doc_type = &commit_title[..end_index];
 
                 if let Some(text_list) = table.get_mut(doc_type) {
-                    text_list.push(title.to_string());
+                    text_list.push(title.clone());
                 } else {
-                    table.insert(doc_type.to_string(), vec![title.to_string()]);
+                    table.insert(doc_type.to_string(), vec![title.clone()]);
+                }
+
+                if let Some(breaking_text) = find_breaking_change(&commit_messages, commit_title, index) {
+                    let breaking_text = linkify_issue_refs(&breaking_text, repo_url, host);
+                    if let Some(text_list) = table.get_mut(BREAKING_CHANGE_KEY) {
+                        text_list.push(breaking_text);
+                    } else {
+                        table.insert(BREAKING_CHANGE_KEY.to_string(), vec![breaking_text]);
+                    }
                 }
             }
         }
@@ -160,7 +434,7 @@ fn get_category_table(commits: &Vec<Commit>, submodule: &str) -> HashMap<String,
 }
 
 /// Returns a string of header info.
-fn get_header(commits: &Vec<Commit>, last_tag: &Tag, submodule: &str) -> String {
+fn get_header(commits: &Vec<Commit>, last_tag: &Tag, submodule: &str, config: &Config, repo_url: &str, host: RemoteHost) -> String {
     let mut result = String::from("");
 
     let version: &str = last_tag.name.split('/').last().expect("Couldn't find the version.");
@@ -172,10 +446,13 @@ fn get_header(commits: &Vec<Commit>, last_tag: &Tag, submodule: &str) -> String
 
     let mut version = Version::parse(version).expect("Couldn't parse the version string.");
     let date = chrono::Local::now();
-    let table = get_category_table(commits, submodule);
-    let bump_type = match table.get("feat") {
-        Some(_) => "minor",
-        None => "patch"
+    let table = get_category_table(commits, submodule, config, repo_url, host);
+    let bump_type = if table.get(BREAKING_CHANGE_KEY).is_some() {
+        "major"
+    } else if table.get("feat").is_some() {
+        "minor"
+    } else {
+        "patch"
     };
     version.bump(bump_type);
 
@@ -250,26 +527,33 @@ impl Version {
 }
 
 /// Returns a string of categorized changes.
-fn get_categorized_changes(commits: &Vec<Commit>, submodule: &str) -> String {
+fn get_categorized_changes(commits: &Vec<Commit>, submodule: &str, config: &Config, repo_url: &str, host: RemoteHost) -> String {
     let mut result = String::from("");
-    let table = get_category_table(commits, submodule);
-
-    // `HashMap::iter()` returns an iterator that yields
-    // (&'a key, &'a value) pairs in arbitrary order.
-    for (key, values) in table.iter() {
-
-        let (category, is_skip) = match key.as_str() {
-            "feat" => ("Features", false),
-            "fix" => ("Bug Fixes", false),
-            "docs" => ("Documentation", false),
-            "style" => ("Styles", true),
-            "refactor" => ("Code Refactoring", true),
-            "test" => ("Test Refactoring", true),
-            "chore" => ("Miscellaneous Chores", true),
-            "perf" => ("Performance Improvements", false),
-            _ => ("Other", true)
+    let table = get_category_table(commits, submodule, config, repo_url, host);
+
+    if let Some(values) = table.get(BREAKING_CHANGE_KEY) {
+        result.push_str("#### \u{26a0} BREAKING CHANGES\n\n");
+        for text in values {
+            result.push_str(&format!("* {}\n", text));
+        }
+        result.push_str("\n");
+    }
+
+    // Render categories in the order the config lists them in (breaking
+    // changes are handled separately above, since they're cross-cutting
+    // rather than a commit type of their own). Any commit type the config
+    // doesn't list falls back to `Config::category`'s "Other" entry, which
+    // is marked skip, so those commits get no section of their own.
+    for key in config.categories.keys().cloned()
+        .chain(table.keys().filter(|k| *k != BREAKING_CHANGE_KEY && !config.categories.contains_key(*k)).cloned()) {
+
+        let values = match table.get(&key) {
+            Some(values) => values,
+            None => continue
         };
 
+        let (category, is_skip) = config.category(&key);
+
         if is_skip {
             continue;
         }
@@ -285,8 +569,85 @@ fn get_categorized_changes(commits: &Vec<Commit>, submodule: &str) -> String {
     result
 }
 
+/// A glob-based include/exclude filter over repo-relative file paths.
+///
+/// A path passes when it matches at least one include pattern (or no
+/// include patterns were configured) and no exclude pattern.
+struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl PathFilter {
+    /// Builds a filter from comma-separated glob lists. An empty string
+    /// means "no patterns", i.e. include everything / exclude nothing.
+    fn new(include_patterns: &str, exclude_patterns: &str) -> PathFilter {
+        PathFilter {
+            include: PathFilter::build_set(include_patterns),
+            exclude: PathFilter::build_set(exclude_patterns),
+        }
+    }
+
+    fn build_set(patterns: &str) -> Option<GlobSet> {
+        if patterns == "" {
+            return None;
+        }
+
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns.split(',') {
+            builder.add(Glob::new(pattern.trim()).expect("Couldn't parse glob pattern."));
+        }
+        Some(builder.build().expect("Couldn't build glob set."))
+    }
+
+    /// Returns whether no patterns were configured at all, i.e. this filter
+    /// passes every path unchanged.
+    fn is_noop(&self) -> bool {
+        self.include.is_none() && self.exclude.is_none()
+    }
+
+    fn is_included(&self, path: &str) -> bool {
+        match &self.include {
+            Some(set) => set.is_match(path),
+            None => true,
+        }
+    }
+
+    fn is_excluded(&self, path: &str) -> bool {
+        match &self.exclude {
+            Some(set) => set.is_match(path),
+            None => false,
+        }
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        self.is_included(path) && !self.is_excluded(path)
+    }
+
+    /// Returns whether `paths` as a whole passes the filter: at least one
+    /// path matches an include pattern, and none match an exclude pattern.
+    fn matches_any(&self, paths: &[String]) -> bool {
+        paths.iter().any(|path| self.is_included(path))
+            && !paths.iter().any(|path| self.is_excluded(path))
+    }
+}
+
+/// Returns the file paths changed by a single commit, relative to its first
+/// parent (or to an empty tree, for a root commit).
+fn get_commit_files(repo: &Repository, commit: &Commit) -> Vec<String> {
+    let new_tree = commit.tree().expect("Couldn't find the commit's tree.");
+    let old_tree = commit.parents().next().and_then(|parent| parent.tree().ok());
+    let diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None).expect("Couldn't diff the commit's trees.");
+
+    diff.deltas()
+        .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+        .filter_map(|path| path.to_str())
+        .map(|path| path.to_string())
+        .collect()
+}
+
 /// Returns a string of files edited since last release.
-fn get_edited_files(repo: &Repository, old: &Commit, new: &Commit, folder: &str) -> String {
+fn get_edited_files(repo: &Repository, old: &Commit, new: &Commit, filter: &PathFilter) -> String {
     let mut result = String::from("");
 
     let old_tree = &old.tree().expect("Couldn't find the old tree.");
@@ -300,11 +661,7 @@ fn get_edited_files(repo: &Repository, old: &Commit, new: &Commit, folder: &str)
     for delta in deltas {
         let filename = delta.old_file().path().expect("Couldn't find the path of old file.");
         let filename = filename.to_str().expect("Couldn't parse file path.");
-        let pattern = match folder {
-            "" => String::from(""),
-            _ => format!("{}/", folder)
-        };
-        if filename.starts_with(&pattern) {
+        if filter.matches(filename) {
             result.push_str(&format!("{}\n", filename));
         }
     }
@@ -314,8 +671,8 @@ fn get_edited_files(repo: &Repository, old: &Commit, new: &Commit, folder: &str)
 }
 
 /// Returns a string of a link to compare changes.
-fn get_compare_changes(repo_url: &str, oid: git2::Oid) -> String {
-    format!("[Compare Changes]({}/compare/{}...HEAD)", repo_url, oid)
+fn get_compare_changes(repo_url: &str, oid: git2::Oid, host: RemoteHost) -> String {
+    format!("[Compare Changes]({})", host.compare_url(repo_url, &oid.to_string(), "HEAD"))
 }
 
 /// Returns a string of the footer.
@@ -323,16 +680,51 @@ fn get_footer() -> String {
     format!("\n\n\nThis PR was generated with [GitRelease](https://github.com/hengfengli/gitrelease).\n")
 }
 
+/// Prepends `section` to the changelog file at `path`, keeping any existing
+/// leading title/preamble above it and every previously generated release
+/// below it. Creates `path` if it doesn't exist yet.
+fn prepend_changelog(path: &str, section: &str) {
+    let existing = fs::read_to_string(path).unwrap_or_default();
+
+    // Each generated release starts with the `:robot:` banner from
+    // `get_header`, so that's where the preamble ends and past releases
+    // begin.
+    let insert_at = existing.find(":robot:").unwrap_or(existing.len());
+    let (preamble, releases) = existing.split_at(insert_at);
+
+    let mut contents = String::from(preamble);
+    contents.push_str(section);
+    contents.push_str(releases);
+
+    fs::write(path, contents).expect("Couldn't write to the changelog file.");
+}
+
 /// Finds out the url of the `origin` remote.
 fn find_origin_remote_url(repo: &Repository) -> String {
     let origin_remote = repo.find_remote("origin").expect("Couldn't find the `origin` remote.");
     let origin_url = origin_remote.url().expect("Failed to read the remote's url.");
 
-    if origin_url.starts_with("https://") {
-        return origin_url.to_string();
+    // Try each of the remote URL shapes git supports: `ssh://host/path`,
+    // `https://host/path.git`, and the scp-like `git@host:path.git`.
+    for regex in &[&*SSH_URL_REGEX, &*HTTPS_URL_REGEX, &*SCP_URL_REGEX] {
+        if let Some(cap) = regex.captures(origin_url) {
+            return format!("https://{}/{}", &cap[1], &cap[2]);
+        }
     }
-    let cap = GITHUB_URL_REGEX.captures_iter(origin_url).next().expect("Failed to read origin url.");
-	format!("https://{}/{}", &cap[1], &cap[2])
+
+    panic!("Couldn't parse the `origin` remote's url: {}", origin_url);
+}
+
+/// Extracts the hostname from a `https://host/path` repo URL, for
+/// `RemoteHost::detect`. `repo_url` is always in this shape, whether it
+/// came from `find_origin_remote_url` or a `repository_url` override.
+fn extract_hostname(repo_url: &str) -> &str {
+    repo_url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or("")
 }
 
 const USAGE: &'static str = "
@@ -340,13 +732,24 @@ Generate a summary of git release.
 
 Usage:
   gitrelease
-  gitrelease [--dir=<path>] [--subdir=<path>] [--submodule=<name>]
+  gitrelease [--dir=<path>] [--subdir=<path>] [--submodule=<name>] [--config=<path>] [--output=<path> | --prepend=<path>] [--include-path=<glob>] [--exclude-path=<glob>]
   gitrelease (-h | --help)
   gitrelease (-v | --version)
 
 Options:
-  -h --help     Show this screen.
-  -v --version  Show version.
+  -h --help             Show this screen.
+  -v --version          Show version.
+  --config=<path>       Path to a `.gitrelease.toml` config file. Defaults
+                        to `<dir>/.gitrelease.toml`.
+  --output=<path>       Write the generated release summary to this file
+                        instead of stdout, overwriting it.
+  --prepend=<path>      Insert the generated release summary at the top of
+                        this file (below any existing title/preamble),
+                        creating it if it doesn't exist.
+  --include-path=<glob> Comma-separated glob patterns; only commits and
+                        files matching at least one are included.
+  --exclude-path=<glob> Comma-separated glob patterns; commits and files
+                        matching any of these are excluded.
 ";
 
 #[derive(Debug, Deserialize)]
@@ -354,6 +757,11 @@ struct Args {
     flag_dir: String,
     flag_subdir: String,
     flag_submodule: String,
+    flag_config: String,
+    flag_output: String,
+    flag_prepend: String,
+    flag_include_path: String,
+    flag_exclude_path: String,
     flag_version: bool,
 }
 
@@ -379,20 +787,171 @@ fn main() {
 
     let subdir = args.flag_subdir;
     let submodule = args.flag_submodule;
+    let config = Config::load(&repo_root, &args.flag_config);
+    let path_filter = PathFilter::new(&args.flag_include_path, &args.flag_exclude_path);
 
     let repo = Repository::open(repo_root.as_str()).expect("Couldn't open repository");
-    let repo_url = &find_origin_remote_url(&repo);
+    let repo_url = &config.repository_url.clone().unwrap_or_else(|| find_origin_remote_url(&repo));
+    let host = config.remote_host().unwrap_or_else(|| RemoteHost::detect(extract_hostname(repo_url)));
 
     if let Some(last_release_tag) = find_commit_for_last_release(&repo, &subdir) {
         let last_commit = find_last_commit(&repo).expect("Failed to find the last commit");
         let commits = find_commits_in_range(&repo, last_commit.id(), last_release_tag.oid);
         let last_release_tag_commit = repo.find_commit(last_release_tag.oid).expect("Failed to find the commit for the last tag.");
 
-        print!("{}", get_header(&commits, &last_release_tag, &submodule));
-        print!("{}", get_categorized_changes(&commits, &submodule));
-        print!("{}", get_commits(&commits, &submodule, repo_url));
-        print!("{}", get_edited_files(&repo, &last_release_tag_commit, &last_commit, &subdir));
-        print!("{}", get_compare_changes(repo_url, last_release_tag.oid));
-        print!("{}", get_footer());
+        let commits: Vec<Commit> = if path_filter.is_noop() {
+            commits
+        } else {
+            commits.into_iter()
+                .filter(|commit| path_filter.matches_any(&get_commit_files(&repo, commit)))
+                .collect()
+        };
+
+        let mut output = String::new();
+        output.push_str(&get_header(&commits, &last_release_tag, &submodule, &config, repo_url, host));
+        output.push_str(&get_categorized_changes(&commits, &submodule, &config, repo_url, host));
+        output.push_str(&get_commits(&commits, &submodule, repo_url, &config, host));
+        output.push_str(&get_closed_issues(&commits, &submodule, repo_url, &config, host));
+        output.push_str(&get_edited_files(&repo, &last_release_tag_commit, &last_commit, &path_filter));
+        output.push_str(&get_compare_changes(repo_url, last_release_tag.oid, host));
+        output.push_str(&get_footer());
+
+        if args.flag_prepend != "" {
+            prepend_changelog(&args.flag_prepend, &output);
+        } else if args.flag_output != "" {
+            fs::write(&args.flag_output, &output).expect("Couldn't write the output file.");
+        } else {
+            print!("{}", output);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_breaking_change_detects_bang_on_unscoped_subject() {
+        let title = "feat!: drop support for old config format";
+        let index = title.find(':').unwrap();
+        let text = find_breaking_change(title, title, index).unwrap();
+        assert_eq!(text, "drop support for old config format");
+    }
+
+    #[test]
+    fn find_breaking_change_detects_bang_on_scoped_subject() {
+        let title = "fix(api)!: remove deprecated field";
+        let index = title.find(':').unwrap();
+        let text = find_breaking_change(title, title, index).unwrap();
+        assert_eq!(text, "remove deprecated field");
+    }
+
+    #[test]
+    fn find_breaking_change_prefers_footer_over_subject() {
+        let messages = "feat!: add widget\n\nBREAKING CHANGE: widgets are now opt-in";
+        let title = messages.lines().next().unwrap();
+        let index = title.find(':').unwrap();
+        let text = find_breaking_change(messages, title, index).unwrap();
+        assert_eq!(text, "widgets are now opt-in");
+    }
+
+    #[test]
+    fn find_breaking_change_none_when_absent() {
+        let title = "feat: add widget";
+        let index = title.find(':').unwrap();
+        assert!(find_breaking_change(title, title, index).is_none());
+    }
+
+    #[test]
+    fn linkify_issue_refs_rewrites_github_style() {
+        let text = linkify_issue_refs("fix bug (#123)", "https://github.com/org/repo", RemoteHost::GitHub);
+        assert_eq!(text, "fix bug ([#123](https://github.com/org/repo/issues/123))");
+    }
+
+    #[test]
+    fn linkify_issue_refs_rewrites_gitlab_style() {
+        let text = linkify_issue_refs("fix bug (#123)", "https://gitlab.com/org/repo", RemoteHost::GitLab);
+        assert_eq!(text, "fix bug ([#123](https://gitlab.com/org/repo/-/issues/123))");
+    }
+
+    #[test]
+    fn remote_host_detect_matches_known_hosts() {
+        assert_eq!(RemoteHost::detect("gitlab.com"), RemoteHost::GitLab);
+        assert_eq!(RemoteHost::detect("bitbucket.org"), RemoteHost::Bitbucket);
+        assert_eq!(RemoteHost::detect("github.com"), RemoteHost::GitHub);
+        assert_eq!(RemoteHost::detect("git.mycorp.internal"), RemoteHost::GitHub);
+    }
+
+    #[test]
+    fn remote_host_detect_ignores_org_and_repo_names() {
+        // A GitHub repo whose name happens to contain "gitlab" must not be
+        // misdetected as a GitLab instance: `extract_hostname` has to strip
+        // the path down to just the host before `detect` ever sees it.
+        let repo_url = "https://github.com/myorg/gitlab-ci-templates";
+        assert_eq!(extract_hostname(repo_url), "github.com");
+        assert_eq!(RemoteHost::detect(extract_hostname(repo_url)), RemoteHost::GitHub);
+    }
+
+    #[test]
+    fn extract_hostname_strips_scheme_and_path() {
+        assert_eq!(extract_hostname("https://github.com/org/repo"), "github.com");
+        assert_eq!(extract_hostname("https://gitlab.example.com/org/repo"), "gitlab.example.com");
+    }
+
+    #[test]
+    fn path_filter_matches_any_requires_include_across_whole_set() {
+        let filter = PathFilter::new("src/**", "");
+        assert!(filter.matches_any(&["src/main.rs".to_string(), "docs/readme.md".to_string()]));
+        assert!(!filter.matches_any(&["docs/readme.md".to_string()]));
+    }
+
+    #[test]
+    fn path_filter_matches_any_excludes_across_whole_set() {
+        // A commit touching both an included and an excluded file must be
+        // dropped entirely, not kept because one file alone passes.
+        let filter = PathFilter::new("src/**", "src/secret.md");
+        assert!(!filter.matches_any(&["src/main.rs".to_string(), "src/secret.md".to_string()]));
+    }
+
+    #[test]
+    fn path_filter_is_noop_without_patterns() {
+        let filter = PathFilter::new("", "");
+        assert!(filter.is_noop());
+        assert!(filter.matches("anything.rs"));
+    }
+
+    #[test]
+    fn config_parses_custom_categories_and_overrides() {
+        let toml = r#"
+            repository_url = "https://git.example.com/org/repo"
+            remote_host = "gitlab"
+            skip_prefixes = ["Release", "chore(deps)"]
+
+            [[categories]]
+            type = "ci"
+            title = "CI"
+            skip = false
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.repository_url.as_deref(), Some("https://git.example.com/org/repo"));
+        assert_eq!(config.remote_host(), Some(RemoteHost::GitLab));
+        assert!(config.should_skip("chore(deps): bump foo"));
+        assert_eq!(config.category("ci"), ("CI", false));
+    }
+
+    #[test]
+    fn config_default_falls_back_to_builtin_categories() {
+        let config = Config::default_config();
+        assert_eq!(config.category("feat"), ("Features", false));
+        assert_eq!(config.category("style"), ("Styles", true));
+        assert_eq!(config.category("unknown"), ("Other", true));
+        assert!(config.should_skip("Release v1.2.3"));
+    }
+
+    #[test]
+    fn version_bump_major_resets_minor_and_patch() {
+        let mut version = Version::parse("1.4.2").unwrap();
+        version.bump("major");
+        assert_eq!(version.to_string(), "2.0.0");
     }
 }